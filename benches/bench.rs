@@ -23,6 +23,13 @@ pub fn gpu_writer_bench(criterion: &mut Criterion) {
       gpu_table.write_into(&mut writer).unwrap();
     })
   });
+  group.bench_function("iter_write_exact_size_mut_slice", |b| {
+    b.iter(|| {
+      let gpu_table = append_gpu_data(EmptyGpuTable, GpuDataIter::<u32, _>::from_exact_size(src.iter().copied()));
+      let mut writer = dst.as_mut_slice();
+      gpu_table.write_into(&mut writer).unwrap();
+    })
+  });
   group.bench_function("slice_write_cursor", |b| {
     b.iter(|| {
       let gpu_table = append_gpu_data(EmptyGpuTable, src.as_slice());
@@ -1,5 +1,5 @@
 use bytemuck::{bytes_of, cast_slice, Pod};
-use std::io::{Error, Write};
+use std::io::{Error, Read, Write};
 
 pub fn append_gpu_data<T: GpuTable, D: GpuData>(gpu_table: T, gpu_data: D) -> impl GpuTable {
   Cons(gpu_data, gpu_table)
@@ -22,13 +22,63 @@ macro_rules! gpu_table {
 pub trait GpuData {
   fn size(&self) -> usize;
   fn write_into<W: Write>(self, writer: &mut W) -> Result<(), Error>;
+
+  /// Byte alignment required for this block's start offset. Must be a
+  /// multiple of 4 so the header's u32-unit offset stays exact. Defaults to
+  /// 4 (no padding beyond the existing u32 granularity).
+  fn alignment(&self) -> usize {
+    std::mem::size_of::<u32>()
+  }
+}
+
+/// Rounds `offset` up to the next multiple of `align`.
+///
+/// `align` must be a multiple of 4 so that the resulting offset, divided by
+/// `size_of::<u32>()`, is still an exact u32 count. `offset` must itself be
+/// an absolute position from the start of the buffer: aligning a
+/// data-region-relative offset and adding the header size back in afterward
+/// does not, in general, land on a multiple of `align`.
+fn align_up(offset: usize, align: usize) -> usize {
+  debug_assert_eq!(align % std::mem::size_of::<u32>(), 0, "alignment must be a multiple of 4");
+  offset.div_ceil(align) * align
+}
+
+/// Writes `pad` zero bytes without a per-call heap allocation.
+fn write_zero_padding<W: Write>(writer: &mut W, pad: usize) -> Result<(), Error> {
+  std::io::copy(&mut std::io::repeat(0).take(pad as u64), writer)?;
+  Ok(())
+}
+
+/// Wraps a block with an explicit start-offset alignment, e.g. 256 for
+/// `min_storage_buffer_offset_alignment` or 16 for std430 `vec4`/`f64`
+/// arrays. The wrapped block's own size and bytes are unchanged; only the
+/// padding inserted *before* it is affected.
+pub struct Aligned<D: GpuData>(pub D, pub usize);
+
+impl<D: GpuData> GpuData for Aligned<D> {
+  fn size(&self) -> usize {
+    self.0.size()
+  }
+  fn write_into<W: Write>(self, writer: &mut W) -> Result<(), Error> {
+    self.0.write_into(writer)
+  }
+  fn alignment(&self) -> usize {
+    self.1
+  }
 }
 
 pub trait GpuTable: GpuData {
   const DATA_COUNT: usize;
-  fn data_size(&self) -> usize;
+
+  /// Size of this table's data region (excluding its own header), in bytes.
+  /// `data_offset` is the absolute byte position the data region starts at
+  /// (i.e. the full header size) and must match what's passed to
+  /// `write_header_into`/`write_data_into`, since alignment padding is
+  /// computed against the absolute buffer position, not a region-relative
+  /// one.
+  fn data_size(&self, data_offset: usize) -> usize;
   fn write_header_into<W: Write>(&self, data_offset: usize, writer: &mut W) -> Result<(), Error>;
-  fn write_data_into<W: Write>(self, writer: &mut W) -> Result<(), Error>;
+  fn write_data_into<W: Write>(self, data_offset: usize, writer: &mut W) -> Result<(), Error>;
 }
 
 pub struct EmptyGpuTable;
@@ -44,13 +94,13 @@ impl GpuData for EmptyGpuTable {
 
 impl GpuTable for EmptyGpuTable {
   const DATA_COUNT: usize = 0;
-  fn data_size(&self) -> usize {
+  fn data_size(&self, _data_offset: usize) -> usize {
     0
   }
   fn write_header_into<W: Write>(&self, _data_offset: usize, _writer: &mut W) -> Result<(), Error> {
     Ok(())
   }
-  fn write_data_into<W: Write>(self, _writer: &mut W) -> Result<(), Error> {
+  fn write_data_into<W: Write>(self, _data_offset: usize, _writer: &mut W) -> Result<(), Error> {
     Ok(())
   }
 }
@@ -59,37 +109,143 @@ struct Cons<D: GpuData, T: GpuTable>(D, T);
 
 impl<D: GpuData, T: GpuTable> GpuData for Cons<D, T> {
   fn size(&self) -> usize {
-    std::mem::size_of::<u32>() * Self::DATA_COUNT + self.data_size()
+    let data_offset = std::mem::size_of::<u32>() * Self::DATA_COUNT;
+    data_offset + self.data_size(data_offset)
   }
   fn write_into<W: Write>(self, writer: &mut W) -> Result<(), Error> {
-    self.write_header_into(std::mem::size_of::<u32>() * Self::DATA_COUNT, writer)?;
-    self.write_data_into(writer)?;
+    let data_offset = std::mem::size_of::<u32>() * Self::DATA_COUNT;
+    self.write_header_into(data_offset, writer)?;
+    self.write_data_into(data_offset, writer)?;
     Ok(())
   }
 }
 
 impl<D: GpuData, T: GpuTable> GpuTable for Cons<D, T> {
   const DATA_COUNT: usize = T::DATA_COUNT + 1;
-  fn data_size(&self) -> usize {
-    self.1.data_size() + self.0.size()
+  fn data_size(&self, data_offset: usize) -> usize {
+    let prior = data_offset + self.1.data_size(data_offset);
+    align_up(prior, self.0.alignment()) - data_offset + self.0.size()
   }
   fn write_header_into<W: Write>(&self, data_offset: usize, writer: &mut W) -> Result<(), Error> {
     self.1.write_header_into(data_offset, writer)?;
-    let offset = data_offset + self.1.data_size();
+    let prior = data_offset + self.1.data_size(data_offset);
+    let offset = align_up(prior, self.0.alignment());
     let offset4 = (offset / std::mem::size_of::<u32>()) as u32;
     writer.write_all(&offset4.to_ne_bytes())?;
     Ok(())
   }
-  fn write_data_into<W: Write>(self, writer: &mut W) -> Result<(), Error> {
-    self.1.write_data_into(writer)?;
+  fn write_data_into<W: Write>(self, data_offset: usize, writer: &mut W) -> Result<(), Error> {
+    let prior = data_offset + self.1.data_size(data_offset);
+    self.1.write_data_into(data_offset, writer)?;
+    let aligned = align_up(prior, self.0.alignment());
+    write_zero_padding(writer, aligned - prior)?;
     self.0.write_into(writer)?;
     Ok(())
   }
 }
 
+/// Object-safe counterpart to [`GpuData`] for blocks whose type isn't known
+/// until runtime. Any `D: GpuData` gets this for free via the blanket impl
+/// below, so the same slice/iterator blocks used with the static
+/// `gpu_table!` macro can be pushed onto a [`DynGpuTable`].
+///
+/// Methods are named `dyn_*` rather than reusing `GpuData`'s names: the
+/// blanket impl below means every `GpuData` type also implements this trait,
+/// and identically-named `&self` methods on two in-scope traits make calls
+/// like `gpu_table.size()` ambiguous (E0034) for any such type.
+pub trait DynGpuData {
+  fn dyn_size(&self) -> usize;
+  fn dyn_alignment(&self) -> usize {
+    std::mem::size_of::<u32>()
+  }
+  fn dyn_write_into(self: Box<Self>, writer: &mut dyn Write) -> Result<(), Error>;
+}
+
+impl<D: GpuData> DynGpuData for D {
+  fn dyn_size(&self) -> usize {
+    GpuData::size(self)
+  }
+  fn dyn_alignment(&self) -> usize {
+    GpuData::alignment(self)
+  }
+  fn dyn_write_into(self: Box<Self>, mut writer: &mut dyn Write) -> Result<(), Error> {
+    GpuData::write_into(*self, &mut writer)
+  }
+}
+
+/// Runtime-assembled counterpart to the compile-time `Cons`/`EmptyGpuTable`
+/// chain, for pipelines where the number and types of blocks aren't known
+/// until runtime (e.g. a variable tensor count). Produces the identical
+/// on-disk layout: a header of `len()` u32 offsets (in u32 units) followed
+/// by the concatenated, alignment-padded blocks, so a `GpuTableView` or
+/// shader can't tell which builder produced the buffer.
+#[derive(Default)]
+pub struct DynGpuTable {
+  blocks: Vec<Box<dyn DynGpuData>>,
+}
+
+impl DynGpuTable {
+  pub fn new() -> Self {
+    DynGpuTable::default()
+  }
+
+  pub fn push(&mut self, data: Box<dyn DynGpuData>) {
+    self.blocks.push(data);
+  }
+
+  pub fn len(&self) -> usize {
+    self.blocks.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.blocks.is_empty()
+  }
+
+  /// Size of the data region (excluding the header), in bytes.
+  ///
+  /// Alignment padding is computed against each block's absolute position in
+  /// the buffer, i.e. starting from the header size rather than 0 — the same
+  /// origin `write_into` necessarily uses, since it starts writing data
+  /// right after the header, and the same one the static `Cons` path uses.
+  /// Starting from 0 here would silently disagree with `write_into` (and
+  /// with the static path) whenever the header size isn't itself a multiple
+  /// of some block's alignment.
+  pub fn data_size(&self) -> usize {
+    let header_size = std::mem::size_of::<u32>() * self.blocks.len();
+    let mut offset = header_size;
+    for block in &self.blocks {
+      offset = align_up(offset, block.dyn_alignment()) + block.dyn_size();
+    }
+    offset - header_size
+  }
+
+  pub fn size(&self) -> usize {
+    std::mem::size_of::<u32>() * self.blocks.len() + self.data_size()
+  }
+
+  pub fn write_into<W: Write>(self, writer: &mut W) -> Result<(), Error> {
+    let header_size = std::mem::size_of::<u32>() * self.blocks.len();
+    let mut offset = header_size;
+    for block in &self.blocks {
+      offset = align_up(offset, block.dyn_alignment());
+      let offset4 = (offset / std::mem::size_of::<u32>()) as u32;
+      writer.write_all(&offset4.to_ne_bytes())?;
+      offset += block.dyn_size();
+    }
+    let mut pos = header_size;
+    for block in self.blocks {
+      let aligned = align_up(pos, block.dyn_alignment());
+      write_zero_padding(writer, aligned - pos)?;
+      pos = aligned + block.dyn_size();
+      block.dyn_write_into(writer)?;
+    }
+    Ok(())
+  }
+}
+
 impl<T: Pod> GpuData for &[T] {
   fn size(&self) -> usize {
-    std::mem::size_of::<T>() * self.len()
+    std::mem::size_of_val(*self)
   }
   fn write_into<W: Write>(self, writer: &mut W) -> Result<(), Error> {
     writer.write_all(cast_slice(self))?;
@@ -109,6 +265,19 @@ impl<T: Pod, I: Clone + Iterator<Item = T>> From<I> for GpuDataIter<T, I> {
   }
 }
 
+impl<T: Pod, I: Iterator<Item = T>> GpuDataIter<T, I> {
+  /// Builds from an `ExactSizeIterator`, reading `len()` directly instead of
+  /// the `Clone`-and-`count()` double pass `From<I>` needs. O(1) and works
+  /// with iterators that can't be cloned.
+  pub fn from_exact_size(iter: I) -> Self
+  where
+    I: ExactSizeIterator,
+  {
+    let size = std::mem::size_of::<T>() * iter.len();
+    GpuDataIter { iter, size }
+  }
+}
+
 impl<T: Pod, I: Iterator<Item = T>> GpuData for GpuDataIter<T, I> {
   fn size(&self) -> usize {
     self.size
@@ -121,6 +290,161 @@ impl<T: Pod, I: Iterator<Item = T>> GpuData for GpuDataIter<T, I> {
   }
 }
 
+/// Read-only, zero-copy overlay over a buffer produced by [`GpuTable::write_into`].
+///
+/// Mirrors the writer side: `T` only carries `DATA_COUNT` so the view knows
+/// how many header offsets to expect, while [`GpuTableView::block`] does the
+/// actual typed re-interpretation of each block, the way a gstreamer map
+/// split keeps the writable and readable halves separate.
+pub struct GpuTableView<'a, T: GpuTable> {
+  data: &'a [u8],
+  _table: std::marker::PhantomData<T>,
+}
+
+impl<'a, T: GpuTable> GpuTableView<'a, T> {
+  pub fn new(data: &'a [u8]) -> Self {
+    GpuTableView { data, _table: std::marker::PhantomData }
+  }
+
+  fn offsets(&self) -> &'a [u32] {
+    cast_slice(&self.data[..T::DATA_COUNT * std::mem::size_of::<u32>()])
+  }
+
+  /// Returns the `index`-th block, reinterpreted as a `&[U]` slice of
+  /// `count` elements starting at that block's offset.
+  ///
+  /// `count` must be the number of `U`s the writer actually put there (the
+  /// caller already knows this from the table's shape): the gap between one
+  /// block's offset and the next includes any alignment padding in front of
+  /// the *next* block, not just this block's own bytes, so slicing up to
+  /// the next offset would read padding as data. The sub-range must also
+  /// start at a `U`-aligned byte position, which an alignment-aware writer
+  /// (e.g. wrapping the block in `Aligned`) guarantees but raw byte offsets
+  /// into arbitrary buffers don't.
+  pub fn block<U: Pod>(&self, index: usize, count: usize) -> &'a [U] {
+    let offsets = self.offsets();
+    let start = offsets[index] as usize * std::mem::size_of::<u32>();
+    let end = start + count * std::mem::size_of::<U>();
+    cast_slice(&self.data[start..end])
+  }
+}
+
+/// Serializes `gpu_table` into a freshly allocated `wgpu::Buffer` and
+/// uploads it via `queue`, so callers don't have to hand-roll the
+/// allocate-a-`Vec`/wrap-in-`Cursor`/`write_into`/`write_buffer` dance
+/// themselves. The returned buffer's header offsets stay in sync with the
+/// bytes it was uploaded with, so it can be bound directly by a shader.
+///
+/// The buffer is created with `mapped_at_creation: false` and filled via
+/// `queue.write_buffer`, which requires `wgpu::BufferUsages::COPY_DST`;
+/// it's added to `usage` automatically so callers only need to specify how
+/// the buffer is actually used (e.g. `STORAGE`, `UNIFORM`).
+#[cfg(feature = "wgpu")]
+pub fn write_gpu_table_to_buffer<T: GpuTable>(
+  device: &wgpu::Device,
+  queue: &wgpu::Queue,
+  gpu_table: T,
+  label: Option<&str>,
+  usage: wgpu::BufferUsages,
+) -> wgpu::Buffer {
+  let mut staging = vec![0u8; gpu_table.size()];
+  let mut writer = std::io::Cursor::new(staging.as_mut_slice());
+  gpu_table.write_into(&mut writer).expect("writing into an in-memory Vec cannot fail");
+  let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+    label,
+    size: staging.len() as wgpu::BufferAddress,
+    usage: usage | wgpu::BufferUsages::COPY_DST,
+    mapped_at_creation: false,
+  });
+  queue.write_buffer(&buffer, 0, &staging);
+  buffer
+}
+
+/// Reusable backing store for serializing the same table shape every frame.
+/// Retains its `Vec<u8>` (and, with the `wgpu` feature, a companion
+/// `wgpu::Buffer`) across calls to [`GpuVec::write`]: capacity is reused
+/// in place when it's big enough, and the GPU buffer is only recreated when
+/// the table grows past it, giving amortized allocation for hot
+/// render/inference loops.
+#[derive(Default)]
+pub struct GpuVec {
+  bytes: Vec<u8>,
+  len: usize,
+  #[cfg(feature = "wgpu")]
+  buffer: Option<wgpu::Buffer>,
+}
+
+impl GpuVec {
+  pub fn new() -> Self {
+    GpuVec::default()
+  }
+
+  /// Serializes `gpu_table` into the retained `Vec<u8>`, growing it only if
+  /// the table no longer fits.
+  pub fn write<T: GpuTable>(&mut self, gpu_table: T) -> Result<(), Error> {
+    let size = gpu_table.size();
+    if self.bytes.len() < size {
+      self.bytes.resize(size, 0);
+    }
+    self.len = size;
+    let mut writer = &mut self.bytes[..size];
+    gpu_table.write_into(&mut writer)
+  }
+
+  /// The bytes written by the most recent [`GpuVec::write`] call.
+  pub fn as_bytes(&self) -> &[u8] {
+    &self.bytes[..self.len]
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Serializes `gpu_table` and uploads it to the retained `wgpu::Buffer`,
+  /// recreating the buffer only when the table has grown past its current
+  /// size.
+  ///
+  /// The buffer is created with `mapped_at_creation: false` and filled via
+  /// `queue.write_buffer`, which requires `wgpu::BufferUsages::COPY_DST`;
+  /// it's added to `usage` automatically so callers only need to specify
+  /// how the buffer is actually used (e.g. `STORAGE`, `UNIFORM`).
+  #[cfg(feature = "wgpu")]
+  pub fn write_to_buffer<T: GpuTable>(
+    &mut self,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    gpu_table: T,
+    label: Option<&str>,
+    usage: wgpu::BufferUsages,
+  ) -> Result<(), Error> {
+    self.write(gpu_table)?;
+    let needs_new_buffer = match &self.buffer {
+      Some(buffer) => buffer.size() < self.len as wgpu::BufferAddress,
+      None => true,
+    };
+    if needs_new_buffer {
+      self.buffer = Some(device.create_buffer(&wgpu::BufferDescriptor {
+        label,
+        size: self.len as wgpu::BufferAddress,
+        usage: usage | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+      }));
+    }
+    queue.write_buffer(self.buffer.as_ref().unwrap(), 0, self.as_bytes());
+    Ok(())
+  }
+
+  /// The buffer most recently filled by [`GpuVec::write_to_buffer`], if any.
+  #[cfg(feature = "wgpu")]
+  pub fn buffer(&self) -> Option<&wgpu::Buffer> {
+    self.buffer.as_ref()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -140,7 +464,7 @@ mod tests {
     ];
     let data_count = data_count(&gpu_table);
     assert_eq!(data_count, 3);
-    assert_eq!(gpu_table.data_size(), 4 * (x.len() + y.len() + 2 * z_count));
+    assert_eq!(gpu_table.data_size(4 * data_count), 4 * (x.len() + y.len() + 2 * z_count));
     assert_eq!(gpu_table.size(), 4 * (data_count + x.len() + y.len() + 2 * z_count));
     let mut v = vec![0u8; gpu_table.size()];
     let mut writer = std::io::Cursor::new(v.as_mut_slice());
@@ -156,4 +480,109 @@ mod tests {
     assert_eq!(slice_f32[6..10], y);
     assert_eq!(slice_u32[10..], [0; 4]);
   }
+
+  #[test]
+  fn test_gpu_writer_alignment() {
+    let x: [u32; 1] = [42];
+    let y: [u8; 3] = [9, 8, 7];
+    let gpu_table = gpu_table![&x as &[u32], Aligned(&y as &[u8], 16),];
+    let data_count = data_count(&gpu_table);
+    let header_size = 4 * data_count;
+    // x (4 bytes) lands right after the header; y is padded out to the next
+    // 16-byte boundary before its 3 bytes.
+    assert_eq!(gpu_table.data_size(header_size), 4 + 4 + 3);
+    assert_eq!(gpu_table.size(), header_size + 4 + 4 + 3);
+    let mut v = vec![0u8; gpu_table.size()];
+    let mut writer = std::io::Cursor::new(v.as_mut_slice());
+    gpu_table.write_into(&mut writer).unwrap();
+    assert_eq!(cast_slice::<u8, u32>(&v[0..8]), [2, 4]);
+    assert_eq!(&v[8..12], &42u32.to_ne_bytes());
+    assert_eq!(&v[12..16], &[0, 0, 0, 0]);
+    assert_eq!(&v[16..19], &y);
+  }
+
+  #[test]
+  fn test_dyn_gpu_table_matches_static_layout() {
+    let x: [u32; 1] = [42];
+    let y: [u8; 3] = [9, 8, 7];
+
+    let static_table = gpu_table![&x as &[u32], Aligned(&y as &[u8], 16),];
+    let mut static_bytes = vec![0u8; static_table.size()];
+    static_table.write_into(&mut std::io::Cursor::new(static_bytes.as_mut_slice())).unwrap();
+
+    let mut dyn_table = DynGpuTable::new();
+    dyn_table.push(Box::new(GpuDataIter::<u32, _>::from(x.into_iter())));
+    dyn_table.push(Box::new(Aligned(GpuDataIter::<u8, _>::from(y.into_iter()), 16)));
+    assert_eq!(dyn_table.size(), static_bytes.len());
+    let mut dyn_bytes = vec![0u8; dyn_table.size()];
+    dyn_table.write_into(&mut std::io::Cursor::new(dyn_bytes.as_mut_slice())).unwrap();
+
+    assert_eq!(dyn_bytes, static_bytes);
+  }
+
+  #[test]
+  fn test_gpu_table_view_round_trip() {
+    let x: [u32; 3] = [1, 2, 3];
+    let y: [u8; 2] = [9, 8];
+
+    // Built directly with `Cons` (rather than the `gpu_table!` macro) so the
+    // table's type is nameable and can be used as `GpuTableView`'s `T`.
+    type Table<'a> = Cons<Aligned<&'a [u8]>, Cons<&'a [u32], EmptyGpuTable>>;
+    let gpu_table: Table = Cons(Aligned(&y as &[u8], 16), Cons(&x as &[u32], EmptyGpuTable));
+    let mut bytes = vec![0u8; gpu_table.size()];
+    gpu_table.write_into(&mut std::io::Cursor::new(bytes.as_mut_slice())).unwrap();
+
+    let view: GpuTableView<Table> = GpuTableView::new(&bytes);
+    assert_eq!(view.block::<u32>(0, x.len()), x);
+    assert_eq!(view.block::<u8>(1, y.len()), y);
+  }
+
+  #[test]
+  fn test_gpu_data_iter_from_exact_size() {
+    let values: [u32; 4] = [10, 20, 30, 40];
+    // `values.iter().copied()` is `Clone`, but going through `ExactSizeIterator`
+    // exercises the `len()`-based size computation `from_exact_size` adds,
+    // rather than `From`'s `Clone`-and-`count()` one.
+    let gpu_data = GpuDataIter::<u32, _>::from_exact_size(values.iter().copied());
+    assert_eq!(gpu_data.size(), values.len() * std::mem::size_of::<u32>());
+
+    let mut v = vec![0u8; gpu_data.size()];
+    gpu_data.write_into(&mut v.as_mut_slice()).unwrap();
+    assert_eq!(cast_slice::<u8, u32>(&v), values);
+  }
+
+  #[test]
+  fn test_gpu_vec_reuse() {
+    let small: [u32; 1] = [7];
+    let large: [u32; 4] = [1, 2, 3, 4];
+
+    let mut gpu_vec = GpuVec::new();
+    gpu_vec.write(gpu_table![&small as &[u32]]).unwrap();
+    let small_expected = {
+      let gpu_table = gpu_table![&small as &[u32]];
+      let mut v = vec![0u8; gpu_table.size()];
+      gpu_table.write_into(&mut v.as_mut_slice()).unwrap();
+      v
+    };
+    assert_eq!(gpu_vec.as_bytes(), small_expected.as_slice());
+    assert_eq!(gpu_vec.len(), small_expected.len());
+
+    // Growing past the retained buffer's size must not leave stale bytes
+    // from the smaller write behind.
+    gpu_vec.write(gpu_table![&large as &[u32]]).unwrap();
+    let large_expected = {
+      let gpu_table = gpu_table![&large as &[u32]];
+      let mut v = vec![0u8; gpu_table.size()];
+      gpu_table.write_into(&mut v.as_mut_slice()).unwrap();
+      v
+    };
+    assert_eq!(gpu_vec.as_bytes(), large_expected.as_slice());
+    assert_eq!(gpu_vec.len(), large_expected.len());
+
+    // Shrinking back down must truncate `as_bytes` to the new, smaller
+    // write rather than exposing leftover bytes from the larger one.
+    gpu_vec.write(gpu_table![&small as &[u32]]).unwrap();
+    assert_eq!(gpu_vec.as_bytes(), small_expected.as_slice());
+    assert_eq!(gpu_vec.len(), small_expected.len());
+  }
 }